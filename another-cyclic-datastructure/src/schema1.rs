@@ -14,13 +14,37 @@ pub enum Type {
     null,
 }
 
+impl Default for Type {
+    fn default() -> Self {
+        Type::null
+    }
+}
+
 #[derive(Deserialize)]
 pub struct TypeInfo {
     #[serde(rename = "type")]
+    #[serde(default)]
     type_: Type,
     #[serde(default)]
     properties: IndexMap<String, Box<TypeInfo>>,
     items: Option<Box<TypeInfo>>,
+    #[serde(default)]
+    required: Vec<String>,
+    #[serde(rename = "anyOf")]
+    #[serde(default)]
+    any_of: Vec<TypeInfo>,
+    #[serde(rename = "oneOf")]
+    #[serde(default)]
+    one_of: Vec<TypeInfo>,
+    #[serde(rename = "allOf")]
+    #[serde(default)]
+    all_of: Vec<TypeInfo>,
+    #[serde(rename = "enum")]
+    #[serde(default)]
+    enum_: Vec<Value>,
+    #[serde(rename = "additionalProperties")]
+    #[serde(default)]
+    additional_properties: Option<bool>,
 }
 
 fn type_of(value: &Value) -> Type {
@@ -40,6 +64,20 @@ fn validate(type_info: &TypeInfo, value: &Value) -> Vec<String> {
     errors
 }
 
+fn path_str(path: &str) -> &str {
+    if path.is_empty() {
+        "/"
+    } else {
+        path
+    }
+}
+
+fn branch_errors(branch: &TypeInfo, value: &Value, path: &mut String) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_inner(branch, value, path, &mut errors);
+    errors
+}
+
 fn validate_inner(
     type_info: &TypeInfo,
     value: &Value,
@@ -51,15 +89,59 @@ fn validate_inner(
         return;
     }
 
+    if !type_info.enum_.is_empty() {
+        if !type_info.enum_.iter().any(|allowed| allowed == value) {
+            errors.push(format!("{}: value not in enum", path_str(path)));
+        }
+        return;
+    }
+
+    if !type_info.all_of.is_empty() {
+        for branch in &type_info.all_of {
+            validate_inner(branch, value, path, errors);
+        }
+        return;
+    }
+
+    if !type_info.any_of.is_empty() {
+        let mut best: Option<Vec<String>> = None;
+        for branch in &type_info.any_of {
+            let branch_errors = branch_errors(branch, value, path);
+            if branch_errors.is_empty() {
+                return;
+            }
+            if best
+                .as_ref()
+                .map_or(true, |b| branch_errors.len() < b.len())
+            {
+                best = Some(branch_errors);
+            }
+        }
+        errors.extend(best.unwrap_or_default());
+        return;
+    }
+
+    if !type_info.one_of.is_empty() {
+        let matched = type_info
+            .one_of
+            .iter()
+            .filter(|branch| branch_errors(branch, value, path).is_empty())
+            .count();
+        if matched != 1 {
+            errors.push(format!(
+                "{}: expected exactly one matching schema, {} matched",
+                path_str(path),
+                matched
+            ));
+        }
+        return;
+    }
+
     let actual_type = type_of(value);
     if actual_type != type_info.type_ {
         errors.push(format!(
             "{}: type mismatch, expected: {:?}, actual: {:?}",
-            if path.is_empty() {
-                "/"
-            } else {
-                path.as_str()
-            },
+            path_str(path),
             type_info.type_,
             actual_type
         ));
@@ -67,12 +149,34 @@ fn validate_inner(
     }
 
     if type_info.type_ == Type::object {
+        let missing = type_info
+            .required
+            .iter()
+            .filter(|name| value.get(name.as_str()).is_none())
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            errors.push(format!(
+                "{}: missing properties: {}",
+                path_str(path),
+                missing.join(", ")
+            ));
+        }
+
         for (key, child_info) in &type_info.properties {
             let len = path.len();
             write!(path, "/{}", key).unwrap();
             validate_inner(child_info, &value[key], path, errors);
             path.truncate(len);
         }
+
+        if type_info.additional_properties == Some(false) {
+            for key in value.as_object().unwrap().keys() {
+                if !type_info.properties.contains_key(key) {
+                    errors.push(format!("{}/{}: no such field", path, key));
+                }
+            }
+        }
     } else if type_info.type_ == Type::array {
         let child_info = type_info.items.as_ref().unwrap();
         for (idx, child_value) in value.as_array().unwrap().iter().enumerate() {
@@ -115,3 +219,113 @@ pub fn test() {
         ],
     );
 }
+
+#[test]
+pub fn test_required() {
+    let schema: TypeInfo = serde_json::from_value(json!({
+        "type": "object",
+        "properties": {
+            "person": {
+                "type": "object",
+                "properties": {
+                    "firstName": {"type": "string"},
+                    "age": {"type": "number"},
+                    "lastName": {"type": "string"}
+                },
+                "required": ["age", "lastName"]
+            }
+        }
+    }))
+    .unwrap();
+
+    run_test(
+        &schema,
+        &json!({"person": {"firstName": "Alex"}}),
+        &["/person: missing properties: age, lastName"],
+    );
+}
+
+#[test]
+pub fn test_enum() {
+    let schema: TypeInfo = serde_json::from_value(json!({"enum": ["a", "b", "c"]})).unwrap();
+
+    run_test(&schema, &json!("a"), &[]);
+    run_test(&schema, &json!("z"), &["/: value not in enum"]);
+}
+
+#[test]
+pub fn test_all_of() {
+    let schema: TypeInfo = serde_json::from_value(json!({
+        "allOf": [
+            {"type": "object", "properties": {"a": {"type": "string"}}},
+            {"type": "object", "required": ["b"]}
+        ]
+    }))
+    .unwrap();
+
+    run_test(
+        &schema,
+        &json!({"a": 1}),
+        &[
+            "/a: type mismatch, expected: string, actual: number",
+            "/: missing properties: b",
+        ],
+    );
+}
+
+#[test]
+pub fn test_any_of() {
+    let schema: TypeInfo = serde_json::from_value(json!({
+        "anyOf": [
+            {"type": "object", "properties": {"a": {"type": "string"}}, "required": ["a"]},
+            {
+                "type": "object",
+                "properties": {"a": {"type": "string"}, "b": {"type": "string"}},
+                "required": ["a", "b"]
+            }
+        ]
+    }))
+    .unwrap();
+
+    // Matches the first branch, so it passes even though the second doesn't.
+    run_test(&schema, &json!({"a": "x"}), &[]);
+
+    // Neither branch matches; the branch with fewer errors (the first) wins.
+    run_test(
+        &schema,
+        &json!({"a": 1}),
+        &["/a: type mismatch, expected: string, actual: number"],
+    );
+}
+
+#[test]
+pub fn test_one_of() {
+    let schema: TypeInfo = serde_json::from_value(json!({
+        "oneOf": [{"type": "string"}, {"type": "number"}]
+    }))
+    .unwrap();
+
+    run_test(&schema, &json!("x"), &[]);
+    run_test(
+        &schema,
+        &json!(true),
+        &["/: expected exactly one matching schema, 0 matched"],
+    );
+}
+
+#[test]
+pub fn test_additional_properties() {
+    let schema: TypeInfo = serde_json::from_value(json!({
+        "type": "object",
+        "properties": {"name": {"type": "string"}},
+        "additionalProperties": false
+    }))
+    .unwrap();
+
+    run_test(&schema, &json!({"name": "Alex"}), &[]);
+    run_test(
+        &schema,
+        &json!({"name": "Alex", "extra": 1}),
+        &["/extra: no such field"],
+    );
+}
@@ -2,9 +2,10 @@
 
 use indexmap::IndexMap;
 use serde_json::{self, Value};
+use std::collections::HashMap;
 use std::fmt::Write;
 
-#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Type {
     object,
     string,
@@ -33,6 +34,23 @@ pub struct SourceTypeInfo {
     ref_: String,
     #[serde(default)]
     definitions: IndexMap<String, Box<SourceTypeInfo>>,
+    #[serde(default)]
+    required: Vec<String>,
+    #[serde(rename = "anyOf")]
+    #[serde(default)]
+    any_of: Vec<SourceTypeInfo>,
+    #[serde(rename = "oneOf")]
+    #[serde(default)]
+    one_of: Vec<SourceTypeInfo>,
+    #[serde(rename = "allOf")]
+    #[serde(default)]
+    all_of: Vec<SourceTypeInfo>,
+    #[serde(rename = "enum")]
+    #[serde(default)]
+    enum_: Vec<Value>,
+    #[serde(rename = "additionalProperties")]
+    #[serde(default)]
+    additional_properties: Option<bool>,
 }
 
 pub struct Schema {
@@ -55,12 +73,56 @@ pub struct TypeInfo {
     type_: Type,
     properties: IndexMap<String, usize>,
     items: usize,
+    required: Vec<String>,
+    any_of: Vec<usize>,
+    one_of: Vec<usize>,
+    all_of: Vec<usize>,
+    enum_: Vec<Value>,
+    additional_properties: Option<bool>,
+}
+
+/// Hashable summary of a finished `TypeInfo`, used to intern structurally
+/// identical nodes under a single index.
+#[derive(PartialEq, Eq, Hash)]
+struct TypeKey {
+    type_: Type,
+    items: usize,
+    properties: Vec<(String, usize)>,
+    required: Vec<String>,
+    any_of: Vec<usize>,
+    one_of: Vec<usize>,
+    all_of: Vec<usize>,
+    // `Value` isn't `Hash`, so key on each member's canonical JSON text instead.
+    enum_: Vec<String>,
+    additional_properties: Option<bool>,
+}
+
+impl TypeKey {
+    fn new(type_info: &TypeInfo) -> Self {
+        TypeKey {
+            type_: type_info.type_,
+            items: type_info.items,
+            properties: type_info
+                .properties
+                .iter()
+                .map(|(k, v)| (k.clone(), *v))
+                .collect(),
+            required: type_info.required.clone(),
+            any_of: type_info.any_of.clone(),
+            one_of: type_info.one_of.clone(),
+            all_of: type_info.all_of.clone(),
+            enum_: type_info.enum_.iter().map(Value::to_string).collect(),
+            additional_properties: type_info.additional_properties,
+        }
+    }
 }
 
 #[derive(Default)]
 struct Translator {
     resolved: IndexMap<String, usize>,
+    interned: HashMap<TypeKey, usize>,
     types: Vec<TypeInfo>,
+    errors: Vec<String>,
 }
 
 impl Translator {
@@ -81,10 +143,33 @@ impl Translator {
             .iter()
             .map(|(k, v)| (k.clone(), self.resolve(v, defs)))
             .collect::<IndexMap<_, _>>();
+
+        let any_of = source
+            .any_of
+            .iter()
+            .map(|s| self.resolve(s, defs))
+            .collect();
+        let one_of = source
+            .one_of
+            .iter()
+            .map(|s| self.resolve(s, defs))
+            .collect();
+        let all_of = source
+            .all_of
+            .iter()
+            .map(|s| self.resolve(s, defs))
+            .collect();
+
         TypeInfo {
             type_: source.type_,
             properties,
             items,
+            required: source.required.clone(),
+            any_of,
+            one_of,
+            all_of,
+            enum_: source.enum_.clone(),
+            additional_properties: source.additional_properties,
         }
     }
 
@@ -95,32 +180,84 @@ impl Translator {
     ) -> usize {
         if source.ref_.is_empty() {
             let t = self.translate(source, defs);
-            self.types.push(t);
-            self.types.len() - 1
-        } else {
-            if let Some(idx) = self.resolved.get(&source.ref_).cloned() {
-                idx
-            } else {
-                let idx = self.types.len();
-                // Put a placeholder first, so we can record its number in the resolved map
-                self.types.push(Default::default());
+            return self.intern(t);
+        }
+
+        if let Some(idx) = self.resolved.get(&source.ref_).cloned() {
+            return idx;
+        }
+
+        let name = match source.ref_.strip_prefix("#/definitions/") {
+            Some(name) => name,
+            None => {
+                self.errors
+                    .push(format!("unsupported $ref syntax: {}", source.ref_));
+                let idx = self.intern(Default::default());
+                // Cache the result under this $ref string so a second use of the
+                // same broken reference doesn't report the same error twice.
                 self.resolved.insert(source.ref_.clone(), idx);
+                return idx;
+            }
+        };
 
-                assert!(source.ref_.starts_with("#/definitions/"));
-                let def = &defs[&source.ref_["#/definitions/".len()..]];
-                self.types[idx] = self.translate(def, defs);
-                idx
+        let def = match defs.get(name) {
+            Some(def) => def,
+            None => {
+                self.errors.push(format!("unknown $ref: {}", source.ref_));
+                let idx = self.intern(Default::default());
+                self.resolved.insert(source.ref_.clone(), idx);
+                return idx;
             }
+        };
+
+        let idx = self.types.len();
+        // Put a placeholder first, so we can record its number in the resolved map
+        self.types.push(Default::default());
+        self.resolved.insert(source.ref_.clone(), idx);
+
+        self.types[idx] = self.translate(def, defs);
+
+        // Only intern now that the body is filled in, so a node that is
+        // still a placeholder never gets treated as a dedup candidate
+        // and distinct recursive types don't collapse prematurely. If an
+        // equal node was already interned, reuse it for this $ref; the slot
+        // we just filled stays in `types`, since nodes translated inside its
+        // body may still point at it.
+        let key = TypeKey::new(&self.types[idx]);
+        if let Some(&existing) = self.interned.get(&key) {
+            self.resolved.insert(source.ref_.clone(), existing);
+            existing
+        } else {
+            self.interned.insert(key, idx);
+            idx
+        }
+    }
+
+    /// Reuse the index of a structurally identical node if one was already
+    /// translated, otherwise allocate a new slot.
+    fn intern(&mut self, type_info: TypeInfo) -> usize {
+        let key = TypeKey::new(&type_info);
+        if let Some(&idx) = self.interned.get(&key) {
+            idx
+        } else {
+            self.types.push(type_info);
+            let idx = self.types.len() - 1;
+            self.interned.insert(key, idx);
+            idx
         }
     }
 }
 
-fn translate(source: &SourceTypeInfo) -> Schema {
+fn translate(source: &SourceTypeInfo) -> Result<Schema, Vec<String>> {
     let mut tx: Translator = Default::default();
     let root = tx.resolve(source, &source.definitions);
-    Schema {
-        types: tx.types,
-        root,
+    if tx.errors.is_empty() {
+        Ok(Schema {
+            types: tx.types,
+            root,
+        })
+    } else {
+        Err(tx.errors)
     }
 }
 
@@ -147,6 +284,25 @@ fn validate(schema: &Schema, value: &Value) -> Vec<String> {
     errors
 }
 
+fn path_str(path: &str) -> &str {
+    if path.is_empty() {
+        "/"
+    } else {
+        path
+    }
+}
+
+fn branch_errors(
+    schema: &Schema,
+    branch: &TypeInfo,
+    value: &Value,
+    path: &mut String,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_inner(schema, branch, value, path, &mut errors);
+    errors
+}
+
 fn validate_inner(
     schema: &Schema,
     type_info: &TypeInfo,
@@ -159,15 +315,61 @@ fn validate_inner(
         return;
     }
 
+    if !type_info.enum_.is_empty() {
+        if !type_info.enum_.iter().any(|allowed| allowed == value) {
+            errors.push(format!("{}: value not in enum", path_str(path)));
+        }
+        return;
+    }
+
+    if !type_info.all_of.is_empty() {
+        for idx in &type_info.all_of {
+            let branch = schema.type_info(*idx);
+            validate_inner(schema, branch, value, path, errors);
+        }
+        return;
+    }
+
+    if !type_info.any_of.is_empty() {
+        let mut best: Option<Vec<String>> = None;
+        for idx in &type_info.any_of {
+            let branch = schema.type_info(*idx);
+            let branch_errors = branch_errors(schema, branch, value, path);
+            if branch_errors.is_empty() {
+                return;
+            }
+            if best
+                .as_ref()
+                .map_or(true, |b| branch_errors.len() < b.len())
+            {
+                best = Some(branch_errors);
+            }
+        }
+        errors.extend(best.unwrap_or_default());
+        return;
+    }
+
+    if !type_info.one_of.is_empty() {
+        let matched = type_info
+            .one_of
+            .iter()
+            .filter(|idx| branch_errors(schema, schema.type_info(**idx), value, path).is_empty())
+            .count();
+        if matched != 1 {
+            errors.push(format!(
+                "{}: expected exactly one matching schema, {} matched",
+                path_str(path),
+                matched
+            ));
+        }
+        return;
+    }
+
     let actual_type = type_of(value);
     if actual_type != type_info.type_ {
         errors.push(format!(
             "{}: type mismatch, expected: {:?}, actual: {:?}",
-            if path.is_empty() {
-                "/"
-            } else {
-                path.as_str()
-            },
+            path_str(path),
             type_info.type_,
             actual_type
         ));
@@ -175,6 +377,20 @@ fn validate_inner(
     }
 
     if type_info.type_ == Type::object {
+        let missing = type_info
+            .required
+            .iter()
+            .filter(|name| value.get(name.as_str()).is_none())
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            errors.push(format!(
+                "{}: missing properties: {}",
+                path_str(path),
+                missing.join(", ")
+            ));
+        }
+
         for (key, child_info) in &type_info.properties {
             let len = path.len();
             write!(path, "/{}", key).unwrap();
@@ -182,6 +398,14 @@ fn validate_inner(
             validate_inner(schema, child_info, &value[key], path, errors);
             path.truncate(len);
         }
+
+        if type_info.additional_properties == Some(false) {
+            for key in value.as_object().unwrap().keys() {
+                if !type_info.properties.contains_key(key) {
+                    errors.push(format!("{}/{}: no such field", path, key));
+                }
+            }
+        }
     } else if type_info.type_ == Type::array {
         let child_info = schema.type_info(type_info.items);
         for (idx, child_value) in value.as_array().unwrap().iter().enumerate() {
@@ -204,7 +428,7 @@ pub fn test() {
     let basic = include_str!("cyclic.schema.json");
     let schema: SourceTypeInfo = serde_json::from_str(basic).unwrap();
 
-    let schema = translate(&schema);
+    let schema = translate(&schema).unwrap();
 
     let val1 = json!("hello");
     run_test(
@@ -245,3 +469,202 @@ pub fn test() {
         ],
     );
 }
+
+#[test]
+pub fn test_required() {
+    let schema: SourceTypeInfo = serde_json::from_value(json!({
+        "type": "object",
+        "properties": {
+            "person": {
+                "type": "object",
+                "properties": {
+                    "firstName": {"type": "string"},
+                    "age": {"type": "number"},
+                    "lastName": {"type": "string"}
+                },
+                "required": ["age", "lastName"]
+            }
+        }
+    }))
+    .unwrap();
+    let schema = translate(&schema).unwrap();
+
+    run_test(
+        &schema,
+        &json!({"person": {"firstName": "Alex"}}),
+        &["/person: missing properties: age, lastName"],
+    );
+}
+
+#[test]
+pub fn test_enum() {
+    let schema: SourceTypeInfo = serde_json::from_value(json!({"enum": ["a", "b", "c"]})).unwrap();
+    let schema = translate(&schema).unwrap();
+
+    run_test(&schema, &json!("a"), &[]);
+    run_test(&schema, &json!("z"), &["/: value not in enum"]);
+}
+
+#[test]
+pub fn test_all_of() {
+    let schema: SourceTypeInfo = serde_json::from_value(json!({
+        "allOf": [
+            {"type": "object", "properties": {"a": {"type": "string"}}},
+            {"type": "object", "required": ["b"]}
+        ]
+    }))
+    .unwrap();
+    let schema = translate(&schema).unwrap();
+
+    run_test(
+        &schema,
+        &json!({"a": 1}),
+        &[
+            "/a: type mismatch, expected: string, actual: number",
+            "/: missing properties: b",
+        ],
+    );
+}
+
+#[test]
+pub fn test_any_of() {
+    let schema: SourceTypeInfo = serde_json::from_value(json!({
+        "anyOf": [
+            {"type": "object", "properties": {"a": {"type": "string"}}, "required": ["a"]},
+            {
+                "type": "object",
+                "properties": {"a": {"type": "string"}, "b": {"type": "string"}},
+                "required": ["a", "b"]
+            }
+        ]
+    }))
+    .unwrap();
+    let schema = translate(&schema).unwrap();
+
+    // Matches the first branch, so it passes even though the second doesn't.
+    run_test(&schema, &json!({"a": "x"}), &[]);
+
+    // Neither branch matches; the branch with fewer errors (the first) wins.
+    run_test(
+        &schema,
+        &json!({"a": 1}),
+        &["/a: type mismatch, expected: string, actual: number"],
+    );
+}
+
+#[test]
+pub fn test_one_of() {
+    let schema: SourceTypeInfo = serde_json::from_value(json!({
+        "oneOf": [{"type": "string"}, {"type": "number"}]
+    }))
+    .unwrap();
+    let schema = translate(&schema).unwrap();
+
+    run_test(&schema, &json!("x"), &[]);
+    run_test(
+        &schema,
+        &json!(true),
+        &["/: expected exactly one matching schema, 0 matched"],
+    );
+}
+
+#[test]
+pub fn test_additional_properties() {
+    let schema: SourceTypeInfo = serde_json::from_value(json!({
+        "type": "object",
+        "properties": {"name": {"type": "string"}},
+        "additionalProperties": false
+    }))
+    .unwrap();
+    let schema = translate(&schema).unwrap();
+
+    run_test(&schema, &json!({"name": "Alex"}), &[]);
+    run_test(
+        &schema,
+        &json!({"name": "Alex", "extra": 1}),
+        &["/extra: no such field"],
+    );
+}
+
+#[test]
+pub fn test_interning_shares_identical_refs() {
+    let schema: SourceTypeInfo = serde_json::from_value(json!({
+        "type": "object",
+        "properties": {
+            "a": {"$ref": "#/definitions/A"},
+            "b": {"$ref": "#/definitions/B"}
+        },
+        "definitions": {
+            "A": {"type": "string"},
+            "B": {"type": "string"}
+        }
+    }))
+    .unwrap();
+    let schema = translate(&schema).unwrap();
+
+    // "A" and "B" translate to structurally identical nodes, so they must
+    // share a single index.
+    assert_eq!(schema.root().properties["a"], schema.root().properties["b"]);
+}
+
+#[test]
+pub fn test_interning_does_not_collapse_distinct_recursive_types() {
+    let schema: SourceTypeInfo = serde_json::from_value(json!({
+        "type": "object",
+        "properties": {
+            "p": {"$ref": "#/definitions/Person"},
+            "t": {"$ref": "#/definitions/Tree"}
+        },
+        "definitions": {
+            "Person": {
+                "type": "object",
+                "properties": {"next": {"$ref": "#/definitions/Person"}}
+            },
+            "Tree": {
+                "type": "object",
+                "properties": {
+                    "next": {"$ref": "#/definitions/Tree"},
+                    "extra": {"type": "string"}
+                }
+            }
+        }
+    }))
+    .unwrap();
+    let schema = translate(&schema).unwrap();
+
+    // While being translated, both "Person" and "Tree" are briefly represented
+    // by a freshly-pushed, still-empty placeholder `TypeInfo`. If interning
+    // keyed on that placeholder instead of the filled-in node, these two
+    // distinct recursive types would incorrectly collapse onto one index.
+    assert_ne!(schema.root().properties["p"], schema.root().properties["t"]);
+}
+
+#[test]
+pub fn test_translate_reports_unknown_ref_once() {
+    let schema: SourceTypeInfo = serde_json::from_value(json!({
+        "type": "object",
+        "properties": {
+            "a": {"$ref": "#/definitions/Missing"},
+            "b": {"$ref": "#/definitions/Missing"}
+        }
+    }))
+    .unwrap();
+
+    let errors = translate(&schema).unwrap_err();
+    assert_eq!(errors, vec!["unknown $ref: #/definitions/Missing"]);
+}
+
+#[test]
+pub fn test_translate_reports_unsupported_ref_syntax_once() {
+    let schema: SourceTypeInfo = serde_json::from_value(json!({
+        "type": "object",
+        "properties": {
+            "a": {"$ref": "Bogus"},
+            "b": {"$ref": "Bogus"}
+        }
+    }))
+    .unwrap();
+
+    let errors = translate(&schema).unwrap_err();
+    assert_eq!(errors, vec!["unsupported $ref syntax: Bogus"]);
+}